@@ -40,16 +40,33 @@
 //! }
 //! ```
 
+pub mod capture;
 pub mod channels;
 
+use core::convert::Infallible;
+
 use crate::{
     init_state::{Disabled, Enabled},
     pac::CTIMER0,
     syscon,
 };
 
+use self::capture::CaptureChannel;
 use self::channels::{state::Detached, Channels};
 
+/// The mode a countdown timer runs in
+///
+/// Passed to [`CTIMER::start`].
+///
+/// [`CTIMER::start`]: struct.CTIMER.html#method.start
+pub enum CountdownMode {
+    /// Stop the timer when it expires
+    OneShot,
+
+    /// Reload and keep running when the timer expires
+    Periodic,
+}
+
 /// Interface to a CTimer peripheral
 ///
 /// Controls the CTimer.  Use [`Peripherals`] to gain access to an instance of
@@ -64,6 +81,12 @@ pub struct CTIMER<State, Channel1State, Channel2State, Channel3State> {
     pub channels: Channels<State, Channel1State, Channel2State, Channel3State>,
 
     inner: CTIMER0,
+    /// The period the timer resets at, i.e. the `period` passed to [`enable`],
+    /// or the `ticks` passed to [`start`] once a countdown has been started.
+    ///
+    /// [`enable`]: #method.enable
+    /// [`start`]: #method.start
+    period: u32,
     _state: State,
 }
 
@@ -72,6 +95,7 @@ impl CTIMER<Disabled, Detached, Detached, Detached> {
         Self {
             channels: Channels::new(),
             inner: ct,
+            period: 0,
             _state: Disabled,
         }
     }
@@ -114,6 +138,7 @@ impl<Channel1State, Channel2State, Channel3State>
         CTIMER {
             channels: Channels::new(),
             inner: self.inner,
+            period,
             _state: Enabled(()),
         }
     }
@@ -142,9 +167,143 @@ impl<Channel1State, Channel2State, Channel3State>
         CTIMER {
             channels: Channels::new(),
             inner: self.inner,
+            period: self.period,
             _state: Disabled,
         }
     }
+
+    /// Use `CAP0` as an input-capture channel
+    ///
+    /// Takes the movable-function input pin that has been assigned to `t0_cap0`
+    /// and returns a [`CaptureChannel`] timestamping edges on it. Use
+    /// [`CaptureChannel::configure`] to select the edges to capture.
+    ///
+    /// [`CaptureChannel`]: capture/struct.CaptureChannel.html
+    /// [`CaptureChannel::configure`]: capture/struct.CaptureChannel.html#method.configure
+    pub fn capture0<PIN>(
+        &self,
+        pin: PIN,
+    ) -> CaptureChannel<capture::Cap0, PIN>
+    where
+        PIN: capture::CaptureInput<capture::Cap0>,
+    {
+        CaptureChannel::new(self.period, pin)
+    }
+
+    /// Use `CAP1` as an input-capture channel
+    ///
+    /// See [`capture0`](#method.capture0).
+    pub fn capture1<PIN>(
+        &self,
+        pin: PIN,
+    ) -> CaptureChannel<capture::Cap1, PIN>
+    where
+        PIN: capture::CaptureInput<capture::Cap1>,
+    {
+        CaptureChannel::new(self.period, pin)
+    }
+
+    /// Use `CAP2` as an input-capture channel
+    ///
+    /// See [`capture0`](#method.capture0).
+    pub fn capture2<PIN>(
+        &self,
+        pin: PIN,
+    ) -> CaptureChannel<capture::Cap2, PIN>
+    where
+        PIN: capture::CaptureInput<capture::Cap2>,
+    {
+        CaptureChannel::new(self.period, pin)
+    }
+
+    /// Use `CAP3` as an input-capture channel
+    ///
+    /// See [`capture0`](#method.capture0).
+    pub fn capture3<PIN>(
+        &self,
+        pin: PIN,
+    ) -> CaptureChannel<capture::Cap3, PIN>
+    where
+        PIN: capture::CaptureInput<capture::Cap3>,
+    {
+        CaptureChannel::new(self.period, pin)
+    }
+}
+
+impl CTIMER<Enabled, Detached, Detached, Detached> {
+    /// Start the timer as a countdown, firing on the MAT3 match
+    ///
+    /// Programs the MAT3 match register with `ticks` and enables its match
+    /// interrupt, giving a second general-purpose hardware timer out of the
+    /// CTimer. In [`Periodic`] mode the counter resets on match and keeps
+    /// running; in [`OneShot`] mode it stops on match. Poll [`wait`] for
+    /// completion, or use [`enable_interrupt`] to drive an interrupt-based
+    /// scheduler.
+    ///
+    /// The countdown drives the MAT3 match register, which is also the PWM
+    /// period register. This method is therefore only available while no PWM
+    /// channel is attached, so the two uses can't clobber each other's match
+    /// register.
+    ///
+    /// [`Periodic`]: enum.CountdownMode.html#variant.Periodic
+    /// [`OneShot`]: enum.CountdownMode.html#variant.OneShot
+    /// [`wait`]: #method.wait
+    /// [`enable_interrupt`]: #method.enable_interrupt
+    pub fn start(&mut self, ticks: u32, mode: CountdownMode) {
+        self.period = ticks;
+        unsafe { self.inner.mr[3].write(|w| w.match_().bits(ticks)) };
+
+        // Make sure a stale flag from a previous run doesn't complete this one
+        // immediately.
+        self.clear_interrupt();
+
+        // Reset the counter, so the countdown measures from zero rather than
+        // from wherever the free-running timer happened to be.
+        self.inner.tcr.modify(|_, w| w.crst().set_bit());
+        self.inner.tcr.modify(|_, w| w.crst().clear_bit());
+
+        self.inner.mcr.modify(|_, w| {
+            w.mr3i().set_bit();
+            match mode {
+                CountdownMode::OneShot => {
+                    w.mr3r().clear_bit();
+                    w.mr3s().set_bit()
+                }
+                CountdownMode::Periodic => {
+                    w.mr3s().clear_bit();
+                    w.mr3r().set_bit()
+                }
+            }
+        });
+
+        // (Re-)start the timer.
+        self.inner.tcr.write(|w| w.cen().set_bit());
+    }
+
+    /// Wait for the countdown to expire
+    ///
+    /// Returns `nb::Result`, so it can be polled in a non-blocking fashion.
+    /// Returns `Ok(())` and clears the flag once the MAT3 match interrupt has
+    /// fired, and `Err(nb::Error::WouldBlock)` otherwise.
+    pub fn wait(&mut self) -> nb::Result<(), Infallible> {
+        if self.inner.ir.read().mr3int().bit_is_set() {
+            self.clear_interrupt();
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Enable the MAT3 match interrupt at the peripheral
+    pub fn enable_interrupt(&mut self) {
+        self.inner.mcr.modify(|_, w| w.mr3i().set_bit());
+    }
+
+    /// Clear the pending MAT3 match interrupt flag
+    pub fn clear_interrupt(&mut self) {
+        // The flag is cleared by writing a `1` to it.
+        self.inner.ir.write(|w| w.mr3int().set_bit());
+    }
 }
 
 impl<State, Channel1State, Channel2State, Channel3State>
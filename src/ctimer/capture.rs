@@ -0,0 +1,186 @@
+//! Input-capture channels of the CTimer
+//!
+//! Capture channels timestamp edges on the timer's `CAPn` inputs, which is
+//! useful for measuring the pulse width, period, or frequency of an external
+//! signal (for example a tachometer or an RC receiver).
+//!
+//! Capture channels are obtained from an [enabled `CTIMER`] via
+//! [`CTIMER::capture0`] and friends, after the corresponding movable function
+//! has been assigned to a pin (the same `assign` mechanism used for the PWM
+//! outputs, e.g. `t0_cap0`). A pin that is used for capture can't be used as a
+//! PWM output at the same time, as the movable function is a singleton that is
+//! consumed by the assignment and then by the capture channel.
+//!
+//! [enabled `CTIMER`]: ../struct.CTIMER.html
+//! [`CTIMER::capture0`]: ../struct.CTIMER.html#method.capture0
+
+use core::marker::PhantomData;
+
+use crate::pac::CTIMER0;
+use crate::swm::{self, state::Assigned, Function};
+
+/// Selects which edges a [`CaptureChannel`] timestamps
+///
+/// [`CaptureChannel`]: struct.CaptureChannel.html
+pub enum CaptureEdge {
+    /// Capture on rising edges only
+    Rising,
+
+    /// Capture on falling edges only
+    Falling,
+
+    /// Capture on both rising and falling edges
+    Both,
+}
+
+/// An input-capture channel of the CTimer
+///
+/// Obtain an instance via [`CTIMER::capture0`] and friends. Please refer to the
+/// [module documentation] for more information.
+///
+/// [`CTIMER::capture0`]: ../struct.CTIMER.html#method.capture0
+/// [module documentation]: index.html
+pub struct CaptureChannel<Id, PIN> {
+    /// The period the timer resets at, used to unwrap elapsed counts.
+    period: u32,
+
+    _id: PhantomData<Id>,
+    // Held by value so the assigned input pin can't be reused elsewhere.
+    _pin: PIN,
+}
+
+impl<Id, PIN> CaptureChannel<Id, PIN>
+where
+    Id: CaptureId,
+    PIN: CaptureInput<Id>,
+{
+    pub(super) fn new(period: u32, pin: PIN) -> Self {
+        Self {
+            period,
+            _id: PhantomData,
+            _pin: pin,
+        }
+    }
+
+    /// Configure the edges this channel captures
+    ///
+    /// Enables edge capture and the capture interrupt for this channel by
+    /// setting the `capnre`/`capnfe`/`capni` bits of the `ccr` register.
+    pub fn configure(&mut self, edge: CaptureEdge) {
+        let (rising, falling) = match edge {
+            CaptureEdge::Rising => (true, false),
+            CaptureEdge::Falling => (false, true),
+            CaptureEdge::Both => (true, true),
+        };
+
+        // Sound, as we only access the stateless `ccr` register and only ever
+        // touch the bits belonging to this channel.
+        let ctimer = unsafe { &*CTIMER0::ptr() };
+        ctimer.ccr.modify(|_, w| match Id::INDEX {
+            0 => w.cap0re().bit(rising).cap0fe().bit(falling).cap0i().set_bit(),
+            1 => w.cap1re().bit(rising).cap1fe().bit(falling).cap1i().set_bit(),
+            2 => w.cap2re().bit(rising).cap2fe().bit(falling).cap2i().set_bit(),
+            _ => w.cap3re().bit(rising).cap3fe().bit(falling).cap3i().set_bit(),
+        });
+    }
+
+    /// Read the value latched at the last captured edge
+    ///
+    /// Returns the count that was latched into the matching `cr[n]` capture
+    /// register the last time a configured edge occurred.
+    pub fn read(&self) -> u32 {
+        // Sound, as `cr` is a read-only register.
+        let ctimer = unsafe { &*CTIMER0::ptr() };
+        ctimer.cr[Id::INDEX].read().cap().bits()
+    }
+
+    /// Compute the number of counts elapsed between two captures
+    ///
+    /// Accounts for the counter wrapping around at the reset value configured
+    /// via `CTIMER::enable` (the MAT3 match), so `end` may be smaller than
+    /// `start`. The MAT3 match resets the counter to `0` on the count *after*
+    /// it reaches `period`, so one full cycle is `period + 1` counts and a
+    /// wrapped measurement runs `start..=period` then `0..=end`; the `+ 1`
+    /// accounts for the step from `period` back to `0`.
+    pub fn elapsed(&self, start: u32, end: u32) -> u32 {
+        if end >= start {
+            end - start
+        } else {
+            // The counter wrapped around at `period`.
+            (self.period - start) + end + 1
+        }
+    }
+}
+
+/// Implemented for each capture channel of the CTimer
+///
+/// This trait is an implementation detail and should not be implemented
+/// outside of this crate.
+pub trait CaptureId: private::Sealed {
+    /// The index of the capture channel (`0` for `CAP0`, `1` for `CAP1`, ...)
+    const INDEX: usize;
+}
+
+/// Implemented for the movable-function inputs that drive a capture channel
+///
+/// Bounding a capture channel's pin on this trait ties it to the `t0_capN`
+/// movable function assigned to a pin, rather than accepting an arbitrary
+/// value. As each movable function is a singleton consumed by `assign`, a
+/// given `CAPn` input can be used for exactly one capture channel, and not for
+/// PWM at the same time.
+///
+/// It is implemented by the SWM movable functions and should not be
+/// implemented outside of this crate.
+pub trait CaptureInput<Id>
+where
+    Id: CaptureId,
+{
+}
+
+macro_rules! capture_ids {
+    ($($id:ident => $index:expr;)*) => {
+        $(
+            /// A capture-channel identifier
+            ///
+            /// See [`CaptureId`](trait.CaptureId.html).
+            pub struct $id(pub(crate) ());
+
+            impl CaptureId for $id {
+                const INDEX: usize = $index;
+            }
+
+            impl private::Sealed for $id {}
+        )*
+    };
+}
+
+capture_ids! {
+    Cap0 => 0;
+    Cap1 => 1;
+    Cap2 => 2;
+    Cap3 => 3;
+}
+
+macro_rules! capture_inputs {
+    ($($id:ident => $function:ident;)*) => {
+        $(
+            // A `CAPn` input is driven by the `T0_CAPn` movable function once
+            // it has been assigned to a pin.
+            impl<PIN> CaptureInput<$id>
+                for Function<swm::$function, Assigned<PIN>>
+            {
+            }
+        )*
+    };
+}
+
+capture_inputs! {
+    Cap0 => T0_CAP0;
+    Cap1 => T0_CAP1;
+    Cap2 => T0_CAP2;
+    Cap3 => T0_CAP3;
+}
+
+mod private {
+    pub trait Sealed {}
+}
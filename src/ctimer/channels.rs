@@ -0,0 +1,205 @@
+//! PWM channels of the CTimer
+//!
+//! The CTimer exposes three PWM channels, driven by the match registers MAT0,
+//! MAT1, and MAT2. Each channel can be attached to a movable-function output
+//! pin (e.g. `t0_mat0`), after which it implements the `embedded-hal` PWM
+//! traits.
+//!
+//! Channels are accessed through the [`channels`] field of an [`CTIMER`].
+//!
+//! [`channels`]: ../struct.CTIMER.html#structfield.channels
+//! [`CTIMER`]: ../struct.CTIMER.html
+
+use core::{convert::Infallible, marker::PhantomData};
+
+use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+
+use crate::{init_state::Enabled, pac::CTIMER0};
+
+use self::state::{Attached, Detached};
+
+/// Type states for the PWM channels
+pub mod state {
+    /// Indicates that a channel is not attached to a pin
+    pub struct Detached;
+
+    /// Indicates that a channel is attached to a pin
+    pub struct Attached;
+}
+
+/// The PWM channels of a CTimer
+///
+/// The type parameters track the state of the timer and each of the three
+/// channels.
+pub struct Channels<State, Channel1State, Channel2State, Channel3State> {
+    /// PWM channel 1, driven by MAT0
+    pub channel1: Channel<State, Mat0, Channel1State>,
+
+    /// PWM channel 2, driven by MAT1
+    pub channel2: Channel<State, Mat1, Channel2State>,
+
+    /// PWM channel 3, driven by MAT2
+    pub channel3: Channel<State, Mat2, Channel3State>,
+}
+
+impl<State, Channel1State, Channel2State, Channel3State>
+    Channels<State, Channel1State, Channel2State, Channel3State>
+{
+    pub(super) fn new() -> Self {
+        Self {
+            channel1: Channel::new(),
+            channel2: Channel::new(),
+            channel3: Channel::new(),
+        }
+    }
+}
+
+/// A single PWM channel of a CTimer
+///
+/// Attach a channel to an output pin with [`attach`]. Once attached and the
+/// timer is enabled, the channel implements the `embedded-hal` PWM traits.
+///
+/// [`attach`]: #method.attach
+pub struct Channel<State, Id, ChannelState> {
+    _state: PhantomData<State>,
+    _id: PhantomData<Id>,
+    _channel_state: PhantomData<ChannelState>,
+}
+
+impl<State, Id, ChannelState> Channel<State, Id, ChannelState> {
+    fn new() -> Self {
+        Self {
+            _state: PhantomData,
+            _id: PhantomData,
+            _channel_state: PhantomData,
+        }
+    }
+}
+
+impl<Id> Channel<Enabled, Id, Detached>
+where
+    Id: MatchId,
+{
+    /// Attach this channel to an output pin
+    ///
+    /// Consumes the movable-function output that has been assigned to the pin,
+    /// so the pin can't be used for anything else while it drives this PWM
+    /// channel. Returns the channel in the [`Attached`] state, which
+    /// implements the `embedded-hal` PWM traits.
+    ///
+    /// [`Attached`]: state/struct.Attached.html
+    pub fn attach<PIN>(self, _pin: PIN) -> Channel<Enabled, Id, Attached> {
+        Channel::new()
+    }
+}
+
+impl<Id> Channel<Enabled, Id, Attached>
+where
+    Id: MatchId,
+{
+    /// The period this channel resets at, i.e. the maximum duty cycle
+    fn max_duty(&self) -> u32 {
+        // Sound, as the MAT3 match register (the period reset) is read-only in
+        // this context.
+        let ctimer = unsafe { &*CTIMER0::ptr() };
+        ctimer.mr[3].read().match_().bits()
+    }
+
+    /// Write the shadow match register for this channel
+    fn write_duty(&mut self, duty: u32) {
+        // Sound, as we only touch the match register belonging to this channel.
+        let ctimer = unsafe { &*CTIMER0::ptr() };
+        unsafe {
+            ctimer.msr[Id::INDEX].write(|w| w.match_shadow().bits(duty))
+        };
+    }
+}
+
+impl<Id> ErrorType for Channel<Enabled, Id, Attached>
+where
+    Id: MatchId,
+{
+    type Error = Infallible;
+}
+
+impl<Id> SetDutyCycle for Channel<Enabled, Id, Attached>
+where
+    Id: MatchId,
+{
+    fn max_duty_cycle(&self) -> u16 {
+        // The trait fixes the duty type at `u16`, while the period is a `u32`.
+        // Saturate rather than truncate, so a period larger than `u16::MAX`
+        // can't wrap to a bogus small maximum.
+        self.max_duty().min(u16::MAX as u32) as u16
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        self.write_duty(duty as u32);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embedded-hal-02")]
+impl<Id> embedded_hal_02::PwmPin for Channel<Enabled, Id, Attached>
+where
+    Id: MatchId,
+{
+    type Duty = u32;
+
+    fn enable(&mut self) {
+        // The PWM output is enabled by `CTIMER::enable`.
+    }
+
+    fn disable(&mut self) {
+        // The PWM output is enabled by `CTIMER::enable`.
+    }
+
+    fn get_duty(&self) -> Self::Duty {
+        let ctimer = unsafe { &*CTIMER0::ptr() };
+        ctimer.mr[Id::INDEX].read().match_().bits()
+    }
+
+    fn get_max_duty(&self) -> Self::Duty {
+        self.max_duty()
+    }
+
+    fn set_duty(&mut self, duty: Self::Duty) {
+        self.write_duty(duty);
+    }
+}
+
+/// Identifies the match register that drives a PWM channel
+///
+/// This trait is an implementation detail and should not be implemented
+/// outside of this crate.
+pub trait MatchId: private::Sealed {
+    /// The index of the match register (`0` for MAT0, `1` for MAT1, ...)
+    const INDEX: usize;
+}
+
+macro_rules! match_ids {
+    ($($id:ident => $index:expr;)*) => {
+        $(
+            /// A match-register identifier
+            ///
+            /// See [`MatchId`](trait.MatchId.html).
+            pub struct $id(());
+
+            impl MatchId for $id {
+                const INDEX: usize = $index;
+            }
+
+            impl private::Sealed for $id {}
+        )*
+    };
+}
+
+match_ids! {
+    Mat0 => 0;
+    Mat1 => 1;
+    Mat2 => 2;
+}
+
+mod private {
+    pub trait Sealed {}
+}
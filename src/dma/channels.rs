@@ -1,6 +1,14 @@
 //! APIs related to DMA channels
 
-use core::marker::PhantomData;
+use core::{
+    cell::RefCell,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use cortex_m::interrupt::{self, Mutex};
 
 use crate::{
     init_state::{Disabled, Enabled},
@@ -8,14 +16,14 @@ use crate::{
         self,
         dma0::{
             channel::{CFG, XFERCFG},
-            ACTIVE0, BUSY0, ENABLESET0, ERRINT0, INTA0, INTB0, INTENCLR0,
-            INTENSET0, SETTRIG0,
+            ABORT0, ACTIVE0, BUSY0, ENABLECLR0, ENABLESET0, ERRINT0, INTA0,
+            INTB0, INTENCLR0, INTENSET0, SETTRIG0,
         },
     },
     reg_proxy::{Reg, RegProxy},
 };
 
-use super::descriptors::ChannelDescriptor;
+use super::descriptors::{ChannelDescriptor, Segment};
 
 /// A DMA channel
 ///
@@ -90,6 +98,300 @@ where
         let registers = SharedRegisters::<C>::new();
         registers.disable_interrupts();
     }
+
+    /// Start an asynchronous DMA transfer
+    ///
+    /// Returns a [`Transfer`] future that, once polled, arms this channel and
+    /// resolves when the DMA controller signals that the transfer has
+    /// completed. The channel's descriptor must have been configured before
+    /// calling this method; `xfercfg` is the raw value written to the
+    /// channel's `XFERCFG` register to describe the transfer. The `SETINTA`
+    /// bit is forced on when arming, so the caller doesn't need to set it for
+    /// the future to be woken.
+    ///
+    /// The returned future must be polled to completion. If it is dropped
+    /// before that, the transfer is aborted (see [`Transfer`]'s `Drop`
+    /// implementation), so the DMA engine stops accessing the transfer
+    /// buffers.
+    ///
+    /// [`Transfer`]: struct.Transfer.html
+    pub fn transfer(&mut self, xfercfg: u32) -> Transfer<C> {
+        Transfer {
+            channel: self,
+            xfercfg,
+            armed: false,
+            completed: false,
+        }
+    }
+
+    /// Start a scatter-gather transfer across a linked list of descriptors
+    ///
+    /// Populates one descriptor per `segment`, links each to the following
+    /// one, and starts the chain. A single started transfer then gathers from
+    /// (or scatters to) every segment's buffer without CPU intervention
+    /// between segments. Completion (the A interrupt, or the channel's
+    /// `ACTIVE0` flag clearing) signals that the whole chain has finished.
+    ///
+    /// In [`ChainMode::Circular`] the last descriptor links back to the first,
+    /// so the chain repeats until the channel is disabled.
+    ///
+    /// # Invariants
+    ///
+    /// `descriptors` must have exactly one entry per segment. It is taken by
+    /// `&'static mut`, as the DMA engine keeps reading the descriptors after
+    /// this method returns; the `ChannelDescriptor` type is aligned to 16
+    /// bytes as the engine requires. The segment buffers must likewise outlive
+    /// the transfer.
+    ///
+    /// [`ChainMode::Circular`]: enum.ChainMode.html#variant.Circular
+    pub fn start_chain(
+        &mut self,
+        segments: &[Segment],
+        descriptors: &'static mut [ChannelDescriptor],
+        mode: ChainMode,
+    ) -> Result<(), ChainError> {
+        if segments.is_empty() || segments.len() != descriptors.len() {
+            return Err(ChainError::DescriptorCountMismatch);
+        }
+        if segments.iter().any(|segment| segment.length == 0) {
+            return Err(ChainError::EmptySegment);
+        }
+        // The `XFERCFG.XFERCOUNT` field is 16 bits wide and holds `length - 1`,
+        // so the largest transfer is 65536 elements.
+        if segments.iter().any(|segment| segment.length > 0x1_0000) {
+            return Err(ChainError::SegmentTooLong);
+        }
+
+        let last = segments.len() - 1;
+        for (index, segment) in segments.iter().enumerate() {
+            // Every descriptor but the final one reloads into the next; in
+            // circular mode the final one reloads too. Raise the A interrupt
+            // on the last segment, so completion of the chain is signalled.
+            let is_last = index == last;
+            let reload = !is_last || matches!(mode, ChainMode::Circular);
+            descriptors[index].configure(segment, reload, is_last);
+        }
+
+        // Link the descriptors together. `split_*` avoids aliasing a mutable
+        // slice while taking a shared reference to the next element.
+        for index in 0..last {
+            let (head, tail) = descriptors.split_at_mut(index + 1);
+            head[index].link_to(&tail[0]);
+        }
+        match mode {
+            ChainMode::OneShot => descriptors[last].clear_link(),
+            ChainMode::Circular => {
+                // Link the tail back to the head. Take the head's address as a
+                // raw pointer first, so we don't hold a shared borrow while
+                // mutably borrowing the tail (the two alias when `last == 0`).
+                let first: *const ChannelDescriptor = &descriptors[0];
+                // Sound: `first` points at `descriptors[0]`, which lives for
+                // `'static`; we only read its address to store as the link.
+                descriptors[last].link_to(unsafe { &*first });
+            }
+        }
+
+        // Copy the head descriptor into the channel's table slot, which is
+        // what the engine reads first, then start the chain from it.
+        let config = descriptors[0].config();
+        *self.descriptor = descriptors[0];
+
+        let registers = SharedRegisters::<C>::new();
+        // Sound, as `config` is a valid `XFERCFG` value built above.
+        self.xfercfg.write(|w| unsafe { w.bits(config) });
+        registers.enable();
+        registers.trigger();
+
+        Ok(())
+    }
+}
+
+/// Controls whether a scatter-gather chain repeats
+///
+/// Passed to [`Channel::start_chain`].
+///
+/// [`Channel::start_chain`]: struct.Channel.html#method.start_chain
+pub enum ChainMode {
+    /// Run the chain once and stop
+    OneShot,
+
+    /// Link the last descriptor back to the first, repeating the chain
+    Circular,
+}
+
+/// An error that can occur while setting up a scatter-gather chain
+#[derive(Debug, Eq, PartialEq)]
+pub enum ChainError {
+    /// The number of descriptors doesn't match the number of segments
+    DescriptorCountMismatch,
+
+    /// A segment has a length of zero
+    EmptySegment,
+
+    /// A segment's length exceeds the 65536-element hardware maximum
+    SegmentTooLong,
+}
+
+/// Number of DMA channels that can be in flight at the same time
+///
+/// This is the maximum over all supported targets, so the waker slice below
+/// can be indexed by [`Instance::INDEX`] regardless of the chip in use.
+const NUM_CHANNELS: usize = 25;
+
+// Used to initialize the waker array below, as `Option<Waker>` is not `Copy`.
+const NO_WAKER: Option<Waker> = None;
+
+/// Wakers for the in-flight transfers, indexed by [`Instance::INDEX`]
+///
+/// The DMA interrupt handler wakes the task waiting on a channel once that
+/// channel's A interrupt has fired.
+static WAKERS: Mutex<RefCell<[Option<Waker>; NUM_CHANNELS]>> =
+    Mutex::new(RefCell::new([NO_WAKER; NUM_CHANNELS]));
+
+/// Latched completion flags, indexed by [`Instance::INDEX`]
+///
+/// The interrupt handler clears the channel's hardware flags, so `poll` can't
+/// observe `a_interrupt_fired` afterwards. It latches completion here instead,
+/// so the primary completion signal doesn't depend on the `is_active` path.
+static COMPLETE: Mutex<RefCell<[bool; NUM_CHANNELS]>> =
+    Mutex::new(RefCell::new([false; NUM_CHANNELS]));
+
+/// The `XFERCFG.SETINTA` bit, which raises the A interrupt on completion
+///
+/// Forced on when arming a [`Transfer`], so completion always wakes the task
+/// regardless of the caller-supplied `xfercfg`.
+const XFERCFG_SETINTA: u32 = 0x1 << 4;
+
+/// An asynchronous DMA transfer
+///
+/// This `Future` is returned by [`Channel::transfer`]. On its first poll it
+/// arms the transfer and enables the channel's A interrupt; it resolves once
+/// the controller has signalled completion.
+///
+/// [`Channel::transfer`]: struct.Channel.html#method.transfer
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Transfer<'a, C>
+where
+    C: Instance,
+{
+    channel: &'a mut Channel<C, Enabled>,
+    xfercfg: u32,
+    armed: bool,
+    completed: bool,
+}
+
+impl<'a, C> Future for Transfer<'a, C>
+where
+    C: Instance,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.completed {
+            return Poll::Ready(());
+        }
+
+        let registers = SharedRegisters::<C>::new();
+
+        // Register the waker before doing anything else, so we can't miss an
+        // interrupt that fires between arming and storing the waker.
+        interrupt::free(|cs| {
+            WAKERS.borrow(cs).borrow_mut()[C::INDEX] =
+                Some(cx.waker().clone());
+        });
+
+        if !self.armed {
+            // Clear any stale completion flag before arming.
+            interrupt::free(|cs| {
+                COMPLETE.borrow(cs).borrow_mut()[C::INDEX] = false;
+            });
+
+            // Force `SETINTA` on, so the A interrupt fires on completion and
+            // wakes the task even if the caller's `xfercfg` didn't set it.
+            // Sound, as `xfercfg` only ever holds a value that was written to
+            // the `XFERCFG` register before.
+            let xfercfg = self.xfercfg | XFERCFG_SETINTA;
+            self.channel.xfercfg.write(|w| unsafe { w.bits(xfercfg) });
+            registers.enable_interrupts();
+            registers.enable();
+            registers.trigger();
+
+            self.armed = true;
+            return Poll::Pending;
+        }
+
+        // The interrupt handler latches completion and clears the hardware
+        // flags, so check the latch first; fall back to the live flags in case
+        // the transfer completed without going through the handler.
+        let complete =
+            interrupt::free(|cs| COMPLETE.borrow(cs).borrow()[C::INDEX]);
+        if complete || registers.a_interrupt_fired() || !registers.is_active() {
+            // Record completion, so `Drop` doesn't abort a finished transfer.
+            self.completed = true;
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<'a, C> Drop for Transfer<'a, C>
+where
+    C: Instance,
+{
+    fn drop(&mut self) {
+        // If the future is dropped while the transfer is still running, abort
+        // it, so the DMA engine doesn't keep writing into a buffer that may no
+        // longer be valid. A transfer that already completed is left alone.
+        if self.armed && !self.completed {
+            let registers = SharedRegisters::<C>::new();
+            registers.disable_interrupts();
+            registers.disable();
+            registers.abort();
+
+            interrupt::free(|cs| {
+                WAKERS.borrow(cs).borrow_mut()[C::INDEX] = None;
+            });
+        }
+    }
+}
+
+/// Handle a DMA interrupt
+///
+/// This function is meant to be called from the crate's `DMA0` interrupt
+/// handler. It inspects the A interrupt flags, clears them for every channel
+/// that completed, and wakes the task waiting on that channel.
+pub fn handle_interrupt() {
+    // Sound, for the same reasons `SharedRegisters` is: we only touch
+    // stateless MMIO registers.
+    let dma = unsafe { &*pac::DMA0::ptr() };
+
+    let fired = dma.inta0.read().ia().bits();
+    if fired == 0 {
+        return;
+    }
+
+    // Clear the flags of all channels that fired, so the interrupt doesn't
+    // re-trigger immediately. This mirrors `SharedRegisters::reset_flags` for
+    // every completed channel at once, including the error flag.
+    dma.errint0.write(|w| unsafe { w.bits(fired) });
+    dma.inta0.write(|w| unsafe { w.bits(fired) });
+    dma.intb0.write(|w| unsafe { w.bits(fired) });
+
+    interrupt::free(|cs| {
+        let mut complete = COMPLETE.borrow(cs).borrow_mut();
+        let mut wakers = WAKERS.borrow(cs).borrow_mut();
+        for index in 0..NUM_CHANNELS {
+            if fired & (0x1 << index) != 0 {
+                // Latch completion, so `poll` sees it even though the hardware
+                // flag has just been cleared.
+                complete[index] = true;
+                if let Some(waker) = wakers[index].take() {
+                    waker.wake();
+                }
+            }
+        }
+    });
 }
 
 /// Implemented for each DMA channel
@@ -113,8 +415,10 @@ pub trait Instance {
 }
 
 pub(super) struct SharedRegisters<C> {
+    abort0: &'static ABORT0,
     active0: &'static ACTIVE0,
     busy0: &'static BUSY0,
+    enableclr0: &'static ENABLECLR0,
     enableset0: &'static ENABLESET0,
     errint0: &'static ERRINT0,
     inta0: &'static INTA0,
@@ -139,8 +443,10 @@ where
             let registers = pac::DMA0::ptr();
 
             Self {
+                abort0: &(*registers).abort0,
                 active0: &(*registers).active0,
                 busy0: &(*registers).busy0,
+                enableclr0: &(*registers).enableclr0,
                 enableset0: &(*registers).enableset0,
                 errint0: &(*registers).errint0,
                 inta0: &(*registers).inta0,
@@ -175,6 +481,20 @@ where
         });
     }
 
+    pub(super) fn disable(&self) {
+        self.enableclr0.write(|w| {
+            // Sound, as all values assigned to `C::FLAG` are valid here.
+            unsafe { w.clr().bits(C::FLAG) }
+        });
+    }
+
+    pub(super) fn abort(&self) {
+        self.abort0.write(|w| {
+            // Sound, as all values assigned to `C::FLAG` are valid here.
+            unsafe { w.abortctrl().bits(C::FLAG) }
+        });
+    }
+
     pub(super) fn trigger(&self) {
         self.settrig0.write(|w| {
             // Sound, as all values assigned to `C::FLAG` are valid here.
@@ -0,0 +1,178 @@
+//! DMA transfer descriptors
+//!
+//! The LPC DMA engine reads a [`ChannelDescriptor`] for each channel from a
+//! table in memory. A descriptor can link to a following one, which lets a
+//! single started transfer gather from, or scatter to, multiple
+//! non-contiguous buffers without CPU intervention between segments (see
+//! [`Channel::start_chain`]).
+//!
+//! [`Channel::start_chain`]: ../channels/struct.Channel.html#method.start_chain
+
+/// The width of a single transferred element
+#[derive(Clone, Copy)]
+pub enum TransferWidth {
+    /// 8-bit transfers
+    Bit8,
+
+    /// 16-bit transfers
+    Bit16,
+
+    /// 32-bit transfers
+    Bit32,
+}
+
+impl TransferWidth {
+    /// The size of one element in bytes
+    fn bytes(self) -> u32 {
+        match self {
+            TransferWidth::Bit8 => 1,
+            TransferWidth::Bit16 => 2,
+            TransferWidth::Bit32 => 4,
+        }
+    }
+
+    /// The encoding of this width in the `XFERCFG.WIDTH` field
+    fn code(self) -> u32 {
+        match self {
+            TransferWidth::Bit8 => 0,
+            TransferWidth::Bit16 => 1,
+            TransferWidth::Bit32 => 2,
+        }
+    }
+}
+
+/// A single segment of a scatter-gather transfer
+#[derive(Clone, Copy)]
+pub struct Segment {
+    /// Start address of the source buffer
+    pub source: *const u8,
+
+    /// Start address of the destination buffer
+    pub destination: *mut u8,
+
+    /// Number of elements to transfer
+    pub length: usize,
+
+    /// The width of a single element
+    pub width: TransferWidth,
+
+    /// Whether to increment the source address after each element
+    ///
+    /// Set this to `false` for a fixed source endpoint, e.g. a peripheral's
+    /// receive data register.
+    pub source_increment: bool,
+
+    /// Whether to increment the destination address after each element
+    ///
+    /// Set this to `false` for a fixed destination endpoint, e.g. a
+    /// peripheral's transmit data register.
+    pub destination_increment: bool,
+}
+
+// Bit positions and flags of the `XFERCFG` field of a descriptor.
+const CFGVALID: u32 = 0x1 << 0;
+const RELOAD: u32 = 0x1 << 1;
+const SETINTA: u32 = 0x1 << 4;
+const WIDTH_SHIFT: u32 = 8;
+const SRCINC_SHIFT: u32 = 12;
+const DSTINC_SHIFT: u32 = 14;
+const XFERCOUNT_SHIFT: u32 = 16;
+// Increment encodings for the `SRCINC`/`DSTINC` fields.
+const INC_NONE: u32 = 0;
+const INC_ONE: u32 = 1;
+
+/// A DMA transfer descriptor
+///
+/// The DMA engine reads one of these per channel from its descriptor table.
+/// Descriptors must be aligned to 16 bytes, and any descriptor linked into a
+/// running chain must live for `'static`, as the DMA engine keeps reading it
+/// after the call that started the transfer has returned.
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+pub struct ChannelDescriptor {
+    config: u32,
+    source_end: u32,
+    dest_end: u32,
+    link: u32,
+}
+
+impl ChannelDescriptor {
+    /// Create a new, empty descriptor
+    pub const fn new() -> Self {
+        Self {
+            config: 0,
+            source_end: 0,
+            dest_end: 0,
+            link: 0,
+        }
+    }
+
+    /// Populate this descriptor to describe a single [`Segment`]
+    ///
+    /// `reload` sets the `XFERCFG.RELOAD` bit, so the engine loads the linked
+    /// descriptor once this segment completes. `interrupt` sets the A
+    /// interrupt, which should be set on the final descriptor of a chain.
+    pub(super) fn configure(
+        &mut self,
+        segment: &Segment,
+        reload: bool,
+        interrupt: bool,
+    ) {
+        let width = segment.width;
+        let count = segment.length as u32;
+
+        let srcinc = if segment.source_increment {
+            INC_ONE
+        } else {
+            INC_NONE
+        };
+        let dstinc = if segment.destination_increment {
+            INC_ONE
+        } else {
+            INC_NONE
+        };
+
+        let mut config = CFGVALID
+            | (width.code() << WIDTH_SHIFT)
+            | (srcinc << SRCINC_SHIFT)
+            | (dstinc << DSTINC_SHIFT)
+            | ((count - 1) << XFERCOUNT_SHIFT);
+        if reload {
+            config |= RELOAD;
+        }
+        if interrupt {
+            config |= SETINTA;
+        }
+        self.config = config;
+
+        // The engine expects the address of the *last* transferred element. For
+        // a fixed endpoint that address is the start address, as it never
+        // advances.
+        let last = (count - 1) * width.bytes();
+        self.source_end = segment.source as u32
+            + if segment.source_increment { last } else { 0 };
+        self.dest_end = segment.destination as u32
+            + if segment.destination_increment { last } else { 0 };
+    }
+
+    /// The raw `XFERCFG` value used to start this descriptor
+    pub(super) fn config(&self) -> u32 {
+        self.config
+    }
+
+    /// Link this descriptor to the one that should run after it
+    pub(super) fn link_to(&mut self, next: &ChannelDescriptor) {
+        self.link = next as *const _ as u32;
+    }
+
+    /// Clear this descriptor's link, marking it as the end of a chain
+    pub(super) fn clear_link(&mut self) {
+        self.link = 0;
+    }
+}
+
+impl Default for ChannelDescriptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,7 @@
 use core::marker::PhantomData;
 
+use fugit::Hertz;
+
 use crate::syscon::{self, clock_source::PeripheralClockSelector};
 
 /// A struct containing the clock configuration for a peripheral
@@ -27,6 +29,100 @@ where
             _clock: PhantomData,
         }
     }
+
+    /// Derive the clock config from a desired SCL frequency
+    ///
+    /// Given the source clock frequency `i2c_clk` and a desired bus frequency
+    /// `scl`, this solves for `divval`, `mstsclhigh`, and `mstscllow`
+    /// automatically. The high and low times are kept in the 2-9 hardware
+    /// range and split as close to 50% duty as possible, and the achieved rate
+    /// is rounded down so the bus never runs faster than requested.
+    ///
+    /// Returns [`Error::UnreachableFrequency`] if no valid combination of
+    /// register values reaches the requested speed.
+    ///
+    /// [`Error::UnreachableFrequency`]: enum.Error.html#variant.UnreachableFrequency
+    pub fn from_frequency(
+        _: &C,
+        i2c_clk: Hertz,
+        scl: Hertz,
+    ) -> Result<Self, Error> {
+        let i2c_clk = i2c_clk.raw();
+        let scl = scl.raw();
+
+        if scl == 0 {
+            return Err(Error::UnreachableFrequency);
+        }
+
+        // The fastest the bus can run is `i2c_clk / 4`, as the smallest
+        // possible high + low divide is `2 + 2`. Anything faster than that is
+        // unreachable.
+        if i2c_clk / 4 < scl {
+            return Err(Error::UnreachableFrequency);
+        }
+
+        // The smallest total divide for which the achieved rate doesn't exceed
+        // the requested one: `i2c_clk / total <= scl`.
+        let target = div_ceil(i2c_clk, scl);
+
+        // Search for the combination that gets closest to (but not above) the
+        // requested rate, i.e. the smallest reachable total divide. Ties are
+        // broken towards a 50% duty cycle.
+        let mut best: Option<(u16, u8, u8)> = None;
+        let mut best_total = u32::MAX;
+        let mut best_balance = u32::MAX;
+
+        for high in 2..=9 {
+            for low in 2..=9 {
+                let duty = high + low;
+                // `prescaler` is `divval + 1`, and has to be at least 1.
+                let prescaler = div_ceil(target, duty).max(1);
+                if prescaler > 0x1_0000 {
+                    continue;
+                }
+
+                let total = prescaler * duty;
+                let balance = (high as i32 - low as i32).unsigned_abs();
+
+                if total < best_total
+                    || (total == best_total && balance < best_balance)
+                {
+                    best_total = total;
+                    best_balance = balance;
+                    best = Some((
+                        (prescaler - 1) as u16,
+                        (high - 2) as u8,
+                        (low - 2) as u8,
+                    ));
+                }
+            }
+        }
+
+        match best {
+            Some((divval, mstsclhigh, mstscllow)) => Ok(Self {
+                divval,
+                mstsclhigh,
+                mstscllow,
+                _clock: PhantomData,
+            }),
+            None => Err(Error::UnreachableFrequency),
+        }
+    }
+}
+
+/// An error that can occur while configuring an I2C [`Clock`]
+///
+/// [`Clock`]: struct.Clock.html
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The requested SCL frequency can't be reached from the source clock
+    UnreachableFrequency,
+}
+
+/// Divide `a` by `b`, rounding the result up
+fn div_ceil(a: u32, b: u32) -> u32 {
+    // Non-overflowing form: `(a + b - 1) / b` would overflow for large `a`.
+    a / b + (a % b != 0) as u32
 }
 
 /// Implemented for I2C clock sources